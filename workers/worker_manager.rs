@@ -1,53 +1,640 @@
-use std::collections::HashMap;
-use std::sync::{Mutex, OnceLock};
-use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// A control-plane instruction delivered to a registered worker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerCommand {
+    Pause,
+    Resume,
+    Cancel,
+}
+
+/// Seconds of silence after which a worker is reported `Idle`.
+pub const IDLE_THRESHOLD_SECS: u64 = 30;
+/// Seconds of silence after which a worker is reported `Dead`.
+pub const DEAD_THRESHOLD_SECS: u64 = 120;
+/// Default time-to-live applied to a registration if it is never refreshed.
+pub const DEFAULT_TTL_SECS: u64 = 3600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerState {
+    Active,
+    Idle,
+    Dead,
+}
 
 #[derive(Debug, Clone)]
 pub struct WorkerInfo {
     pub id: String,
     pub version: String,
     pub registered_at: u64,
+    pub last_heartbeat: u64,
+    pub last_command: Option<WorkerCommand>,
+    pub ttl_secs: u64,
+    /// True for entries rehydrated by `load_from` that have not yet
+    /// re-registered; their `command_tx` has no live receiver.
+    restored: bool,
+    command_tx: Sender<WorkerCommand>,
 }
 
 impl WorkerInfo {
-    pub fn new(id: &str, version: Option<&str>) -> Self {
+    fn new(id: &str, version: Option<&str>, command_tx: Sender<WorkerCommand>) -> Self {
         let version = match version {
             Some(v) if !v.is_empty() => v.to_string(),
             _ => "Uncommitted".to_string(),
         };
-        let registered_at = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_secs();
-        Self { id: id.to_string(), version, registered_at }
+        let registered_at = now_secs();
+        Self {
+            id: id.to_string(),
+            version,
+            registered_at,
+            last_heartbeat: registered_at,
+            last_command: None,
+            ttl_secs: DEFAULT_TTL_SECS,
+            restored: false,
+            command_tx,
+        }
     }
 }
 
-static WORKERS: OnceLock<Mutex<HashMap<String, WorkerInfo>>> = OnceLock::new();
+/// Whether a registration is still fresh enough to be served: `now` must be
+/// within `ttl_secs` of the most recent activity (registration or the latest
+/// heartbeat), so a worker that keeps heartbeating never expires — only one
+/// that goes silent for longer than its TTL does.
+fn should_retain(info: &WorkerInfo, now: u64) -> bool {
+    let freshest = info.registered_at.max(info.last_heartbeat);
+    now <= freshest.saturating_add(info.ttl_secs)
+}
+
+/// A worker's last-known info paired with its liveness, derived at read time.
+#[derive(Debug, Clone)]
+pub struct WorkerStatus {
+    pub info: WorkerInfo,
+    pub state: WorkerState,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+}
+
+fn worker_state(last_heartbeat: u64, now: u64) -> WorkerState {
+    let elapsed = now.saturating_sub(last_heartbeat);
+    if elapsed >= DEAD_THRESHOLD_SECS {
+        WorkerState::Dead
+    } else if elapsed >= IDLE_THRESHOLD_SECS {
+        WorkerState::Idle
+    } else {
+        WorkerState::Active
+    }
+}
+
+static WORKERS: OnceLock<RwLock<HashMap<String, WorkerInfo>>> = OnceLock::new();
 
 pub fn start_workers() {
-    WORKERS.get_or_init(|| Mutex::new(HashMap::new()));
+    WORKERS.get_or_init(|| RwLock::new(HashMap::new()));
 }
 
-pub fn register_worker(id: &str, version: Option<&str>) {
+/// Registers a worker and returns the receiving end of its command channel.
+/// The worker is expected to hold onto the `Receiver` and poll it in its run
+/// loop to react to `Pause`/`Resume`/`Cancel` instructions.
+pub fn register_worker(id: &str, version: Option<&str>) -> Receiver<WorkerCommand> {
     start_workers();
+    let (command_tx, command_rx) = mpsc::channel();
     if let Some(map_mutex) = WORKERS.get() {
-        let mut map = map_mutex.lock().unwrap();
-        let info = WorkerInfo::new(id, version);
+        let mut map = map_mutex.write().unwrap();
+        let info = WorkerInfo::new(id, version, command_tx);
         println!(
             "[AUDIT] {} worker registered with version '{}' at {}",
             info.id, info.version, info.registered_at
         );
         map.insert(id.to_string(), info);
+        REGISTRATIONS_TOTAL.fetch_add(1, Ordering::Relaxed);
+    }
+    autosave();
+    command_rx
+}
+
+/// Sends a control-plane command to a registered worker. Returns an error if
+/// the worker id is not registered (including TTL-expired entries, which are
+/// treated as absent the same way `list_workers`/`schedule_at` do), was
+/// restored from persistence and has not yet re-registered, or its receiver
+/// has otherwise been dropped.
+pub fn send_command(id: &str, cmd: WorkerCommand) -> Result<(), String> {
+    start_workers();
+    let map_mutex = WORKERS.get().ok_or_else(|| "worker registry not started".to_string())?;
+    let mut map = map_mutex.write().unwrap();
+    let now = now_secs();
+    let info = map.get_mut(id).ok_or_else(|| format!("unknown worker '{id}'"))?;
+    if !should_retain(info, now) {
+        return Err(format!("unknown worker '{id}'"));
+    }
+    if info.restored {
+        return Err(format!(
+            "worker '{id}' was restored from persistence and has not re-registered; no command channel is available yet"
+        ));
     }
+    info.command_tx
+        .send(cmd)
+        .map_err(|_| format!("worker '{id}' is no longer listening"))?;
+    info.last_command = Some(cmd);
+    println!("[AUDIT] {id} worker command delivered: {cmd:?}");
+    Ok(())
 }
 
-pub fn list_workers() -> Vec<WorkerInfo> {
+/// Updates the heartbeat timestamp for a registered worker. No-op if the
+/// worker id is not registered, including TTL-expired entries — the same
+/// "does this worker exist" rule `list_workers`/`send_command`/`schedule_at`
+/// apply, so a lapsed registration can't be kept alive by a stray heartbeat.
+pub fn heartbeat(id: &str) {
+    start_workers();
+    if let Some(map_mutex) = WORKERS.get() {
+        let mut map = map_mutex.write().unwrap();
+        let now = now_secs();
+        if let Some(info) = map.get_mut(id) {
+            if !should_retain(info, now) {
+                return;
+            }
+            info.last_heartbeat = now;
+            let state = worker_state(info.last_heartbeat, now);
+            println!(
+                "[AUDIT] {} worker heartbeat at {} (state: {:?})",
+                info.id, info.last_heartbeat, state
+            );
+        }
+    }
+}
+
+pub fn list_workers() -> Vec<WorkerStatus> {
     WORKERS
         .get()
         .map(|m| {
-            let map = m.lock().unwrap();
-            map.values().cloned().collect()
+            let map = m.read().unwrap();
+            let now = now_secs();
+            map.values()
+                .filter(|info| should_retain(info, now))
+                .cloned()
+                .map(|info| {
+                    let state = worker_state(info.last_heartbeat, now);
+                    WorkerStatus { info, state }
+                })
+                .collect()
         })
         .unwrap_or_default()
 }
+
+/// Physically removes registrations whose TTL has elapsed, returning the
+/// number of entries removed.
+pub fn sweep() -> usize {
+    let Some(map_mutex) = WORKERS.get() else {
+        return 0;
+    };
+    let mut map = map_mutex.write().unwrap();
+    let now = now_secs();
+    let before = map.len();
+    map.retain(|_, info| should_retain(info, now));
+    let removed = before - map.len();
+    DEREGISTRATIONS_TOTAL.fetch_add(removed as u64, Ordering::Relaxed);
+    removed
+}
+
+/// Removes registrations whose heartbeat is older than `max_age`, returning
+/// the number of entries evicted.
+pub fn evict_dead(max_age: Duration) -> usize {
+    let Some(map_mutex) = WORKERS.get() else {
+        return 0;
+    };
+    let mut map = map_mutex.write().unwrap();
+    let now = now_secs();
+    let max_age_secs = max_age.as_secs();
+    let before = map.len();
+    map.retain(|_, info| now.saturating_sub(info.last_heartbeat) <= max_age_secs);
+    let evicted = before - map.len();
+    DEREGISTRATIONS_TOTAL.fetch_add(evicted as u64, Ordering::Relaxed);
+    evicted
+}
+
+// --- Metrics ---------------------------------------------------------------
+
+static REGISTRATIONS_TOTAL: AtomicU64 = AtomicU64::new(0);
+static DEREGISTRATIONS_TOTAL: AtomicU64 = AtomicU64::new(0);
+static HISTOGRAMS: OnceLock<Mutex<HashMap<String, Vec<u64>>>> = OnceLock::new();
+
+/// Total number of workers ever registered.
+pub fn registrations_total() -> u64 {
+    REGISTRATIONS_TOTAL.load(Ordering::Relaxed)
+}
+
+/// Total number of workers ever removed from the registry.
+pub fn deregistrations_total() -> u64 {
+    DEREGISTRATIONS_TOTAL.load(Ordering::Relaxed)
+}
+
+/// Gauge of how many workers are currently registered and not yet
+/// TTL-expired, matching what `list_workers` reports (raw map entries whose
+/// TTL lapsed but haven't been `sweep()`-ed are not counted as live).
+pub fn live_worker_count() -> usize {
+    WORKERS
+        .get()
+        .map(|m| {
+            let map = m.read().unwrap();
+            let now = now_secs();
+            map.values().filter(|info| should_retain(info, now)).count()
+        })
+        .unwrap_or(0)
+}
+
+fn record_duration_ms(label: String, millis: u64) {
+    let map_mutex = HISTOGRAMS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut map = map_mutex.lock().unwrap();
+    map.entry(label).or_default().push(millis);
+}
+
+/// The recorded millisecond samples for a given histogram label, in the
+/// order they were observed.
+pub fn histogram_samples(label: &str) -> Vec<u64> {
+    HISTOGRAMS
+        .get()
+        .map(|m| m.lock().unwrap().get(label).cloned().unwrap_or_default())
+        .unwrap_or_default()
+}
+
+/// RAII guard returned by [`time_worker`]. Records the elapsed time between
+/// construction and drop into the histogram for its label.
+pub struct TimingGuard {
+    label: String,
+    start: Instant,
+}
+
+impl Drop for TimingGuard {
+    fn drop(&mut self) {
+        let millis = self.start.elapsed().as_millis() as u64;
+        record_duration_ms(self.label.clone(), millis);
+    }
+}
+
+/// Starts timing a unit of work for `id`, keyed by `id@version` when the
+/// worker is registered (or by `id` alone otherwise). Drop the returned
+/// guard to record the elapsed duration into the histogram.
+pub fn time_worker(id: &str) -> TimingGuard {
+    let label = WORKERS
+        .get()
+        .and_then(|m| {
+            m.read()
+                .unwrap()
+                .get(id)
+                .filter(|info| should_retain(info, now_secs()))
+                .map(|info| format!("{id}@{}", info.version))
+        })
+        .unwrap_or_else(|| id.to_string());
+    TimingGuard { label, start: Instant::now() }
+}
+
+// --- Scheduler ---------------------------------------------------------------
+
+/// A command queued against a worker for dispatch once its release time is
+/// reached.
+#[derive(Debug, Clone)]
+pub struct ScheduledTask {
+    pub worker_id: String,
+    pub command: WorkerCommand,
+}
+
+static SCHEDULE: OnceLock<Mutex<BTreeMap<u64, Vec<ScheduledTask>>>> = OnceLock::new();
+
+/// Queues `command` for `worker_id`, to become due at the UNIX epoch second
+/// `when`. Fails if the worker is not currently registered, using the same
+/// TTL freshness check as `list_workers`/`sweep` so the scheduler and the
+/// rest of the registry agree on what "registered" means.
+pub fn schedule_at(worker_id: &str, when: u64, command: WorkerCommand) -> Result<(), String> {
+    let registered = WORKERS
+        .get()
+        .map(|m| {
+            let map = m.read().unwrap();
+            map.get(worker_id).is_some_and(|info| should_retain(info, now_secs()))
+        })
+        .unwrap_or(false);
+    if !registered {
+        return Err(format!("unknown worker '{worker_id}'"));
+    }
+    let map_mutex = SCHEDULE.get_or_init(|| Mutex::new(BTreeMap::new()));
+    let mut map = map_mutex.lock().unwrap();
+    map.entry(when)
+        .or_default()
+        .push(ScheduledTask { worker_id: worker_id.to_string(), command });
+    Ok(())
+}
+
+/// Drains and returns every scheduled task whose release time is `<= now`.
+pub fn release_due(now: u64) -> Vec<ScheduledTask> {
+    let Some(map_mutex) = SCHEDULE.get() else {
+        return Vec::new();
+    };
+    let mut map = map_mutex.lock().unwrap();
+    let due_keys: Vec<u64> = map.range(..=now).map(|(key, _)| *key).collect();
+    let mut due = Vec::new();
+    for key in due_keys {
+        if let Some(tasks) = map.remove(&key) {
+            due.extend(tasks);
+        }
+    }
+    due
+}
+
+// --- Persistence -------------------------------------------------------------
+
+/// The serializable subset of `WorkerInfo` written to disk. Excludes the
+/// command channel, which cannot outlive the process.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PersistedWorker {
+    id: String,
+    version: String,
+    registered_at: u64,
+    ttl_secs: u64,
+}
+
+static AUTOSAVE_PATH: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+static AUTOSAVE_DIRTY: AtomicBool = AtomicBool::new(false);
+static AUTOSAVE_THREAD: OnceLock<()> = OnceLock::new();
+/// Window over which back-to-back registrations are coalesced into a single
+/// flush, so registration churn doesn't serialize disk I/O into every call.
+const AUTOSAVE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Configures a path to which the registry is flushed, debounced, after
+/// registrations. Pass this before calling `register_worker` to keep a
+/// restart-safe snapshot on disk.
+pub fn set_autosave_path(path: &str) {
+    let lock = AUTOSAVE_PATH.get_or_init(|| Mutex::new(None));
+    *lock.lock().unwrap() = Some(path.to_string());
+}
+
+/// Marks the registry dirty and ensures the background flush thread is
+/// running; the actual `save_to` happens off the caller's thread, at most
+/// once per `AUTOSAVE_DEBOUNCE` window.
+fn autosave() {
+    if AUTOSAVE_PATH.get().and_then(|lock| lock.lock().unwrap().clone()).is_none() {
+        return;
+    }
+    AUTOSAVE_DIRTY.store(true, Ordering::SeqCst);
+    AUTOSAVE_THREAD.get_or_init(|| {
+        std::thread::spawn(|| loop {
+            std::thread::sleep(AUTOSAVE_DEBOUNCE);
+            if !AUTOSAVE_DIRTY.swap(false, Ordering::SeqCst) {
+                continue;
+            }
+            let Some(path) = AUTOSAVE_PATH.get().and_then(|lock| lock.lock().unwrap().clone())
+            else {
+                continue;
+            };
+            if let Err(err) = save_to(&path) {
+                println!("[AUDIT] worker registry autosave to '{path}' failed: {err}");
+            }
+        });
+    });
+}
+
+/// Serializes the current registry to `path` as JSON.
+pub fn save_to(path: &str) -> std::io::Result<()> {
+    let snapshot: Vec<PersistedWorker> = WORKERS
+        .get()
+        .map(|m| {
+            m.read()
+                .unwrap()
+                .values()
+                .map(|info| PersistedWorker {
+                    id: info.id.clone(),
+                    version: info.version.clone(),
+                    registered_at: info.registered_at,
+                    ttl_secs: info.ttl_secs,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let json = serde_json::to_string_pretty(&snapshot)
+        .map_err(std::io::Error::other)?;
+    std::fs::write(path, json)
+}
+
+/// Rehydrates the registry from a snapshot written by `save_to`. Restored
+/// workers are marked `Dead` (their heartbeat reset to the epoch) and have
+/// no usable command channel (the receiver side cannot be persisted) until
+/// they next call `register_worker`, which replaces the entry with a fresh,
+/// connected channel. `send_command` rejects restored-but-not-reregistered
+/// workers with a distinct error rather than a generic "not listening" one.
+/// Returns the number of workers restored.
+pub fn load_from(path: &str) -> std::io::Result<usize> {
+    start_workers();
+    let contents = std::fs::read_to_string(path)?;
+    let snapshot: Vec<PersistedWorker> = serde_json::from_str(&contents)
+        .map_err(std::io::Error::other)?;
+    let map_mutex = WORKERS.get().expect("start_workers was just called");
+    let mut map = map_mutex.write().unwrap();
+    let restored = snapshot.len();
+    for persisted in snapshot {
+        let (command_tx, _command_rx) = mpsc::channel();
+        map.insert(
+            persisted.id.clone(),
+            WorkerInfo {
+                id: persisted.id,
+                version: persisted.version,
+                registered_at: persisted.registered_at,
+                last_heartbeat: 0,
+                last_command: None,
+                ttl_secs: persisted.ttl_secs,
+                restored: true,
+                command_tx,
+            },
+        );
+    }
+    println!("[AUDIT] worker registry restored {restored} worker(s) from '{path}'");
+    Ok(restored)
+}
+
+/// Starts the registry and rehydrates it from a prior snapshot at `path`, if
+/// one exists. Restored workers are not reachable via `send_command` until
+/// they call `register_worker` again.
+pub fn start_workers_from(path: &str) -> std::io::Result<usize> {
+    start_workers();
+    if std::path::Path::new(path).exists() {
+        load_from(path)
+    } else {
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn worker_state_thresholds() {
+        assert_eq!(worker_state(1_000, 1_000), WorkerState::Active);
+        assert_eq!(worker_state(1_000, 1_000 + IDLE_THRESHOLD_SECS - 1), WorkerState::Active);
+        assert_eq!(worker_state(1_000, 1_000 + IDLE_THRESHOLD_SECS), WorkerState::Idle);
+        assert_eq!(worker_state(1_000, 1_000 + DEAD_THRESHOLD_SECS - 1), WorkerState::Idle);
+        assert_eq!(worker_state(1_000, 1_000 + DEAD_THRESHOLD_SECS), WorkerState::Dead);
+    }
+
+    #[test]
+    fn heartbeat_refreshes_last_heartbeat() {
+        let id = "test_chunk0_1_heartbeat_worker";
+        let _rx = register_worker(id, None);
+        let before = now_secs();
+        std::thread::sleep(Duration::from_millis(1_100));
+        heartbeat(id);
+        let status = list_workers().into_iter().find(|s| s.info.id == id).unwrap();
+        assert!(status.info.last_heartbeat >= before);
+        assert_eq!(status.state, WorkerState::Active);
+    }
+
+    #[test]
+    fn send_command_delivers_to_receiver() {
+        let id = "test_chunk0_2_command_worker";
+        let rx = register_worker(id, None);
+        send_command(id, WorkerCommand::Pause).unwrap();
+        assert_eq!(rx.recv().unwrap(), WorkerCommand::Pause);
+        let status = list_workers().into_iter().find(|s| s.info.id == id).unwrap();
+        assert_eq!(status.info.last_command, Some(WorkerCommand::Pause));
+    }
+
+    #[test]
+    fn send_command_rejects_unknown_worker() {
+        let err = send_command("test_chunk0_2_missing_worker", WorkerCommand::Cancel).unwrap_err();
+        assert!(err.contains("unknown worker"));
+    }
+
+    #[test]
+    fn metrics_track_registrations_and_durations() {
+        let id = "test_chunk0_3_metrics_worker";
+        let registrations_before = registrations_total();
+        let _rx = register_worker(id, None);
+        assert!(registrations_total() > registrations_before);
+        {
+            let _guard = time_worker(id);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let label = format!("{id}@Uncommitted");
+        let samples = histogram_samples(&label);
+        assert_eq!(samples.len(), 1);
+    }
+
+    #[test]
+    fn should_retain_ttl_boundary() {
+        let info = WorkerInfo {
+            id: "test_chunk0_4_ttl_worker".to_string(),
+            version: "v1".to_string(),
+            registered_at: 1_000,
+            last_heartbeat: 1_000,
+            last_command: None,
+            ttl_secs: 60,
+            restored: false,
+            command_tx: mpsc::channel().0,
+        };
+        assert!(should_retain(&info, 1_000 + 60));
+        assert!(!should_retain(&info, 1_000 + 61));
+    }
+
+    #[test]
+    fn heartbeat_and_send_command_reject_ttl_expired_worker() {
+        let id = "test_chunk0_4_expired_worker";
+        let _rx = register_worker(id, None);
+        {
+            let map_mutex = WORKERS.get().unwrap();
+            let mut map = map_mutex.write().unwrap();
+            let info = map.get_mut(id).unwrap();
+            info.registered_at = 0;
+            info.last_heartbeat = 0;
+            info.ttl_secs = 1;
+        }
+
+        assert!(list_workers().into_iter().all(|s| s.info.id != id));
+
+        heartbeat(id);
+        let map_mutex = WORKERS.get().unwrap();
+        assert_eq!(map_mutex.read().unwrap().get(id).unwrap().last_heartbeat, 0);
+
+        let err = send_command(id, WorkerCommand::Pause).unwrap_err();
+        assert!(err.contains("unknown worker"));
+    }
+
+    #[test]
+    fn list_workers_serves_concurrent_readers() {
+        let id = "test_chunk0_5_rwlock_worker";
+        let _rx = register_worker(id, None);
+        let handles: Vec<_> = (0..8).map(|_| std::thread::spawn(list_workers)).collect();
+        for handle in handles {
+            let workers = handle.join().unwrap();
+            assert!(workers.iter().any(|status| status.info.id == id));
+        }
+    }
+
+    #[test]
+    fn release_due_drains_only_elapsed_tasks() {
+        let id = "test_chunk0_6_schedule_worker";
+        let _rx = register_worker(id, None);
+        schedule_at(id, 5_000, WorkerCommand::Resume).unwrap();
+
+        assert!(release_due(4_999).is_empty());
+        let due = release_due(5_000);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].worker_id, id);
+        assert_eq!(due[0].command, WorkerCommand::Resume);
+        assert!(release_due(5_000).is_empty());
+    }
+
+    #[test]
+    fn schedule_at_rejects_unknown_worker() {
+        let err = schedule_at("test_chunk0_6_missing_worker", 1, WorkerCommand::Pause).unwrap_err();
+        assert!(err.contains("unknown worker"));
+    }
+
+    #[test]
+    fn persistence_round_trip_marks_restored_workers_unreachable() {
+        let id = "test_chunk0_7_persisted_worker";
+        let _rx = register_worker(id, None);
+        let path = std::env::temp_dir()
+            .join(format!("arcanos_worker_registry_test_{}.json", std::process::id()));
+        let path_str = path.to_str().unwrap();
+
+        save_to(path_str).unwrap();
+        let restored = load_from(path_str).unwrap();
+        assert!(restored > 0);
+
+        let err = send_command(id, WorkerCommand::Cancel).unwrap_err();
+        assert!(err.contains("restored from persistence"));
+
+        let status = list_workers().into_iter().find(|s| s.info.id == id).unwrap();
+        assert_eq!(status.state, WorkerState::Dead);
+
+        let _rx2 = register_worker(id, None);
+        send_command(id, WorkerCommand::Cancel).unwrap();
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn autosave_flushes_dirty_registry_after_debounce_window() {
+        let id = "test_chunk0_7_autosave_worker";
+        let path = std::env::temp_dir()
+            .join(format!("arcanos_worker_registry_autosave_test_{}.json", std::process::id()));
+        let path_str = path.to_str().unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        set_autosave_path(path_str);
+        let _rx = register_worker(id, None);
+
+        std::thread::sleep(AUTOSAVE_DEBOUNCE * 3);
+
+        let contents = std::fs::read_to_string(&path)
+            .expect("autosave background thread should have written the snapshot by now");
+        assert!(contents.contains(id));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}